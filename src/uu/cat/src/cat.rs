@@ -13,6 +13,8 @@
 #[macro_use]
 extern crate quick_error;
 #[cfg(unix)]
+extern crate libc;
+#[cfg(unix)]
 extern crate unix_socket;
 #[macro_use]
 extern crate uucore;
@@ -20,17 +22,25 @@ extern crate uucore;
 // last synced with: cat (GNU coreutils) 8.13
 use quick_error::ResultExt;
 use std::fs::{metadata, File};
-use std::io::{self, stderr, stdin, stdout, BufWriter, Read, Write};
-use uucore::fs::is_stdin_interactive;
+use std::io::{self, stderr, stdin, stdout, BufWriter, LineWriter, Read, Stdout, Write};
 
 /// Unix domain socket support
 #[cfg(unix)]
-use std::net::Shutdown;
-#[cfg(unix)]
 use std::os::unix::fs::FileTypeExt;
 #[cfg(unix)]
 use unix_socket::UnixStream;
 
+/// TCP stream support, shared with the Unix-socket path above
+use std::net::{Shutdown, TcpListener, TcpStream};
+
+/// Kernel-side zero-copy support (`copy_file_range`/`sendfile`)
+#[cfg(target_os = "linux")]
+use std::mem;
+#[cfg(target_os = "linux")]
+use std::os::unix::io::{AsRawFd, RawFd};
+#[cfg(target_os = "linux")]
+use std::ptr;
+
 #[cfg(target_os = "wasi")]
 use std::os::wasi::fs::FileTypeExt;
 
@@ -103,8 +113,35 @@ struct OutputOptions {
 
 /// Represents an open file handle, stream, or other device
 struct InputHandle {
-    reader: Box<dyn Read>,
-    is_interactive: bool,
+    reader: InputReader,
+}
+
+/// Concrete source of bytes backing an `InputHandle`.
+///
+/// A plain `Box<dyn Read>` would erase the underlying descriptor type,
+/// which defeats the kernel-side zero-copy fast path in `write_fast`:
+/// `copy_file_range`/`sendfile` need a real `File` to pull a raw fd
+/// from. Keeping a concrete variant per source lets `write_fast`
+/// recover that fd for `File` sources while every other caller still
+/// just treats this as an ordinary `Read`.
+enum InputReader {
+    File(File),
+    Stdin(io::Stdin),
+    #[cfg(unix)]
+    Socket(UnixStream),
+    Tcp(TcpStream),
+}
+
+impl Read for InputReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match *self {
+            InputReader::File(ref mut f) => f.read(buf),
+            InputReader::Stdin(ref mut s) => s.read(buf),
+            #[cfg(unix)]
+            InputReader::Socket(ref mut s) => s.read(buf),
+            InputReader::Tcp(ref mut s) => s.read(buf),
+        }
+    }
 }
 
 /// Concrete enum of recognized file types.
@@ -234,43 +271,147 @@ fn get_input_type(path: &str) -> CatResult<InputType> {
 /// Returns an InputHandle from which a Reader can be accessed or an
 /// error
 ///
+/// Besides plain paths and `-` for stdin, `path` may also be
+/// `tcp://host:port` to connect to a remote socket, or
+/// `tcp-listen://host:port` to accept a single incoming connection,
+/// letting `cat` act as a tiny netcat-style reader.
+///
 /// # Arguments
 ///
 /// * `path` - `InputHandler` will wrap a reader from this file path
 fn open(path: &str) -> CatResult<InputHandle> {
     if path == "-" {
-        let stdin = stdin();
         return Ok(InputHandle {
-            reader: Box::new(stdin) as Box<dyn Read>,
-            is_interactive: is_stdin_interactive(),
+            reader: InputReader::Stdin(stdin()),
         });
     }
 
+    if let Some(addr) = path.strip_prefix("tcp://") {
+        let stream = TcpStream::connect(addr).context(path)?;
+        return connected_stream(stream, path, InputReader::Tcp);
+    }
+
+    if let Some(addr) = path.strip_prefix("tcp-listen://") {
+        let listener = TcpListener::bind(addr).context(path)?;
+        let (stream, _) = listener.accept().context(path)?;
+        return connected_stream(stream, path, InputReader::Tcp);
+    }
+
     match get_input_type(path)? {
         InputType::Directory => Err(CatError::IsDirectory(path.to_owned())),
         #[cfg(unix)]
         InputType::Socket => {
             let socket = UnixStream::connect(path).context(path)?;
-            socket.shutdown(Shutdown::Write).context(path)?;
-            Ok(InputHandle {
-                reader: Box::new(socket) as Box<dyn Read>,
-                is_interactive: false,
-            })
+            connected_stream(socket, path, InputReader::Socket)
         }
         _ => {
             let file = File::open(path).context(path)?;
             Ok(InputHandle {
-                reader: Box::new(file) as Box<dyn Read>,
-                is_interactive: false,
+                reader: InputReader::File(file),
             })
         }
     }
 }
 
+/// A connected stream-based source (TCP or Unix-domain socket) that
+/// can have its write half shut down.
+trait HalfCloseable {
+    fn shutdown_write(&self) -> io::Result<()>;
+}
+
+impl HalfCloseable for TcpStream {
+    fn shutdown_write(&self) -> io::Result<()> {
+        self.shutdown(Shutdown::Write)
+    }
+}
+
+#[cfg(unix)]
+impl HalfCloseable for UnixStream {
+    fn shutdown_write(&self) -> io::Result<()> {
+        self.shutdown(Shutdown::Write)
+    }
+}
+
+/// Finishes setting up a connected stream-based input source: shuts
+/// down the write half, so the peer sees EOF once `cat` stops writing
+/// to it, then wraps `stream` as an `InputHandle` via `reader` (an
+/// `InputReader` variant, e.g. `InputReader::Tcp`).
+fn connected_stream<S: HalfCloseable>(
+    stream: S,
+    path: &str,
+    reader: impl FnOnce(S) -> InputReader,
+) -> CatResult<InputHandle> {
+    stream.shutdown_write().context(path)?;
+    Ok(InputHandle {
+        reader: reader(stream),
+    })
+}
+
+/// Size of each kernel-side transfer attempted by the zero-copy fast
+/// path; large enough to amortize the syscall, small enough to keep a
+/// single call's latency reasonable.
+#[cfg(target_os = "linux")]
+const FAST_COPY_CHUNK: usize = 1024 * 1024 * 4;
+
+/// `true` if the open file descriptor `fd` refers to a regular file.
+///
+/// Used to decide between `copy_file_range` (regular file to regular
+/// file) and `sendfile` (regular file to pipe/socket/other fd).
+#[cfg(target_os = "linux")]
+fn is_fd_regular_file(fd: RawFd) -> bool {
+    let mut stat: libc::stat = unsafe { mem::zeroed() };
+    if unsafe { libc::fstat(fd, &mut stat) } != 0 {
+        return false;
+    }
+    stat.st_mode & libc::S_IFMT == libc::S_IFREG
+}
+
+/// Attempts to move `src` to `dst_fd` entirely in the kernel, using
+/// `copy_file_range(2)` when `dst` is also a regular file and
+/// `sendfile(2)` otherwise.
+///
+/// Returns `true` if `src` was copied to EOF this way. Returns `false`
+/// if the syscall is unavailable (e.g. `ENOSYS`, `EINVAL`, `EXDEV`) or
+/// returns an error, in which case the caller should fall back to the
+/// buffered read/write loop -- `src`'s file offset has already been
+/// advanced by whatever the fast path managed to copy, so the fallback
+/// picks up right where it left off.
+#[cfg(target_os = "linux")]
+fn copy_file_fast(src: &File, dst_fd: RawFd, dst_is_regular_file: bool) -> bool {
+    let src_fd = src.as_raw_fd();
+    loop {
+        let n = unsafe {
+            if dst_is_regular_file {
+                libc::copy_file_range(
+                    src_fd,
+                    ptr::null_mut(),
+                    dst_fd,
+                    ptr::null_mut(),
+                    FAST_COPY_CHUNK,
+                    0,
+                )
+            } else {
+                libc::sendfile(dst_fd, src_fd, ptr::null_mut(), FAST_COPY_CHUNK)
+            }
+        };
+
+        if n < 0 {
+            return false;
+        } else if n == 0 {
+            return true;
+        }
+    }
+}
+
 /// Writes files to stdout with no configuration.  This allows a
 /// simple memory copy. Returns `Ok(())` if no errors were
 /// encountered, or an error with the number of errors encountered.
 ///
+/// When a source is a regular file, this first tries to hand the
+/// transfer entirely to the kernel (see `copy_file_fast`), the same
+/// specialization `std::io::copy` applies internally, before falling
+/// back to the buffered read/write loop below.
+///
 /// # Arguments
 ///
 /// * `files` - There is no short circuit when encountering an error
@@ -280,14 +421,38 @@ fn write_fast(files: Vec<String>) -> CatResult<()> {
     let mut in_buf = [0; 1024 * 64];
     let mut error_count = 0;
 
+    #[cfg(target_os = "linux")]
+    let dst_is_regular_file = is_fd_regular_file(writer.as_raw_fd());
+
     for file in files {
         match open(&file[..]) {
             Ok(mut handle) => {
-                while let Ok(n) = handle.reader.read(&mut in_buf) {
-                    if n == 0 {
-                        break;
+                #[cfg(target_os = "linux")]
+                {
+                    if let InputReader::File(ref src) = handle.reader {
+                        // Drain anything still sitting in Stdout's internal
+                        // buffer from a prior file before writing straight to
+                        // its raw fd, or the raw write could reach the kernel
+                        // ahead of that buffered tail and reorder output.
+                        writer.flush().context(&file[..])?;
+                        if copy_file_fast(src, writer.as_raw_fd(), dst_is_regular_file) {
+                            continue;
+                        }
+                    }
+                }
+
+                loop {
+                    let read_result: CatResult<usize> =
+                        handle.reader.read(&mut in_buf).context(&file[..]);
+                    match read_result {
+                        Ok(0) => break,
+                        Ok(n) => writer.write_all(&in_buf[..n]).context(&file[..])?,
+                        Err(error) => {
+                            writeln!(&mut stderr(), "{}", error)?;
+                            error_count += 1;
+                            break;
+                        }
                     }
-                    writer.write_all(&in_buf[..n]).context(&file[..])?;
                 }
             }
             Err(error) => {
@@ -303,6 +468,66 @@ fn write_fast(files: Vec<String>) -> CatResult<()> {
     }
 }
 
+/// `true` if stdout is connected to a terminal.
+#[cfg(unix)]
+fn is_stdout_interactive() -> bool {
+    unsafe { libc::isatty(libc::STDOUT_FILENO) == 1 }
+}
+
+#[cfg(not(unix))]
+fn is_stdout_interactive() -> bool {
+    false
+}
+
+/// Output sink for the line-by-line writing path.
+///
+/// Picks `LineWriter` (flush-on-newline) when stdout is a terminal, so
+/// interactive sessions still see output promptly, and `BufWriter`
+/// otherwise, so redirected output isn't flushed after every line.
+/// Built once per `cat` invocation and threaded through `OutputState`,
+/// rather than re-allocated per file.
+enum OutputSink {
+    Line(LineWriter<Stdout>),
+    Buffered(BufWriter<Stdout>),
+}
+
+impl OutputSink {
+    fn new() -> Self {
+        if is_stdout_interactive() {
+            OutputSink::Line(LineWriter::new(stdout()))
+        } else {
+            OutputSink::Buffered(BufWriter::with_capacity(1024 * 64, stdout()))
+        }
+    }
+
+    /// Unwraps the underlying writer, inspecting the result so a
+    /// deferred write error (e.g. a broken pipe) surfaces as a
+    /// `CatError::Output` instead of being silently swallowed when the
+    /// writer is dropped.
+    fn finish(self) -> CatResult<()> {
+        match self {
+            OutputSink::Line(w) => w.into_inner().map(|_| ()).map_err(|e| e.into_error().into()),
+            OutputSink::Buffered(w) => w.into_inner().map(|_| ()).map_err(|e| e.into_error().into()),
+        }
+    }
+}
+
+impl Write for OutputSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match *self {
+            OutputSink::Line(ref mut w) => w.write(buf),
+            OutputSink::Buffered(ref mut w) => w.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match *self {
+            OutputSink::Line(ref mut w) => w.flush(),
+            OutputSink::Buffered(ref mut w) => w.flush(),
+        }
+    }
+}
+
 /// State that persists between output of each file
 struct OutputState {
     /// The current line number
@@ -310,6 +535,9 @@ struct OutputState {
 
     /// Whether the output cursor is at the beginning of a new line
     at_line_start: bool,
+
+    /// The sink all files are written to
+    writer: OutputSink,
 }
 
 /// Writes files to stdout with `options` as configuration.  Returns
@@ -319,18 +547,35 @@ struct OutputState {
 /// # Arguments
 ///
 /// * `files` - There is no short circuit when encountering an error
-/// reading a file in this vector
+/// reading a file in this vector, unless that error is a `CatError::Output`:
+/// a broken output destination affects every remaining file identically, so
+/// further files are skipped, matching how `write_fast` gives up on the
+/// first write failure instead of retrying it once per file.
 fn write_lines(files: Vec<String>, options: &OutputOptions) -> CatResult<()> {
     let mut error_count = 0;
     let mut state = OutputState {
         line_number: 1,
         at_line_start: true,
+        writer: OutputSink::new(),
     };
 
+    let mut output_broken = false;
     for file in files {
         if let Err(error) = write_file_lines(&file, options, &mut state) {
+            let is_output_error = matches!(error, CatError::Output(_));
             writeln!(&mut stderr(), "{}", error).context(&file[..])?;
             error_count += 1;
+            if is_output_error {
+                output_broken = true;
+                break;
+            }
+        }
+    }
+
+    if !output_broken {
+        if let Err(error) = state.writer.finish() {
+            writeln!(&mut stderr(), "{}", error)?;
+            error_count += 1;
         }
     }
 
@@ -345,10 +590,10 @@ fn write_lines(files: Vec<String>, options: &OutputOptions) -> CatResult<()> {
 fn write_file_lines(file: &str, options: &OutputOptions, state: &mut OutputState) -> CatResult<()> {
     let mut handle = open(file)?;
     let mut in_buf = [0; 1024 * 31];
-    let mut writer = BufWriter::with_capacity(1024 * 64, stdout());
     let mut one_blank_kept = false;
 
-    while let Ok(n) = handle.reader.read(&mut in_buf) {
+    loop {
+        let n = handle.reader.read(&mut in_buf).context(file)?;
         if n == 0 {
             break;
         }
@@ -360,13 +605,10 @@ fn write_file_lines(file: &str, options: &OutputOptions, state: &mut OutputState
                 if !state.at_line_start || !options.squeeze_blank || !one_blank_kept {
                     one_blank_kept = true;
                     if state.at_line_start && options.number == NumberingMode::All {
-                        write!(&mut writer, "{0:6}\t", state.line_number)?;
+                        write!(&mut state.writer, "{0:6}\t", state.line_number)?;
                         state.line_number += 1;
                     }
-                    writer.write_all(options.end_of_line.as_bytes())?;
-                    if handle.is_interactive {
-                        writer.flush().context(&file[..])?;
-                    }
+                    state.writer.write_all(options.end_of_line.as_bytes())?;
                 }
                 state.at_line_start = true;
                 pos += 1;
@@ -374,17 +616,17 @@ fn write_file_lines(file: &str, options: &OutputOptions, state: &mut OutputState
             }
             one_blank_kept = false;
             if state.at_line_start && options.number != NumberingMode::None {
-                write!(&mut writer, "{0:6}\t", state.line_number)?;
+                write!(&mut state.writer, "{0:6}\t", state.line_number)?;
                 state.line_number += 1;
             }
 
             // print to end of line or end of buffer
             let offset = if options.show_nonprint {
-                write_nonprint_to_end(&in_buf[pos..], &mut writer, options.tab.as_bytes())
+                write_nonprint_to_end(&in_buf[pos..], &mut state.writer, options.tab.as_bytes())?
             } else if options.show_tabs {
-                write_tab_to_end(&in_buf[pos..], &mut writer)
+                write_tab_to_end(&in_buf[pos..], &mut state.writer)?
             } else {
-                write_to_end(&in_buf[pos..], &mut writer)
+                write_to_end(&in_buf[pos..], &mut state.writer)?
             };
             // end of buffer?
             if offset == 0 {
@@ -392,10 +634,7 @@ fn write_file_lines(file: &str, options: &OutputOptions, state: &mut OutputState
                 break;
             }
             // print suitable end of line
-            writer.write_all(options.end_of_line.as_bytes())?;
-            if handle.is_interactive {
-                writer.flush()?;
-            }
+            state.writer.write_all(options.end_of_line.as_bytes())?;
             state.at_line_start = true;
             pos += offset;
         }
@@ -407,42 +646,42 @@ fn write_file_lines(file: &str, options: &OutputOptions, state: &mut OutputState
 // write***_to_end methods
 // Write all symbols till end of line or end of buffer is reached
 // Return the (number of written symbols + 1) or 0 if the end of buffer is reached
-fn write_to_end<W: Write>(in_buf: &[u8], writer: &mut W) -> usize {
-    match in_buf.iter().position(|c| *c == b'\n') {
+fn write_to_end<W: Write>(in_buf: &[u8], writer: &mut W) -> io::Result<usize> {
+    Ok(match in_buf.iter().position(|c| *c == b'\n') {
         Some(p) => {
-            writer.write_all(&in_buf[..p]).unwrap();
+            writer.write_all(&in_buf[..p])?;
             p + 1
         }
         None => {
-            writer.write_all(in_buf).unwrap();
+            writer.write_all(in_buf)?;
             0
         }
-    }
+    })
 }
 
-fn write_tab_to_end<W: Write>(mut in_buf: &[u8], writer: &mut W) -> usize {
+fn write_tab_to_end<W: Write>(mut in_buf: &[u8], writer: &mut W) -> io::Result<usize> {
     let mut count = 0;
     loop {
         match in_buf.iter().position(|c| *c == b'\n' || *c == b'\t') {
             Some(p) => {
-                writer.write_all(&in_buf[..p]).unwrap();
+                writer.write_all(&in_buf[..p])?;
                 if in_buf[p] == b'\n' {
-                    return count + p + 1;
+                    return Ok(count + p + 1);
                 } else {
-                    writer.write_all(b"^I").unwrap();
+                    writer.write_all(b"^I")?;
                     in_buf = &in_buf[p + 1..];
                     count += p + 1;
                 }
             }
             None => {
-                writer.write_all(in_buf).unwrap();
-                return 0;
+                writer.write_all(in_buf)?;
+                return Ok(0);
             }
         };
     }
 }
 
-fn write_nonprint_to_end<W: Write>(in_buf: &[u8], writer: &mut W, tab: &[u8]) -> usize {
+fn write_nonprint_to_end<W: Write>(in_buf: &[u8], writer: &mut W, tab: &[u8]) -> io::Result<usize> {
     let mut count = 0;
 
     for byte in in_buf.iter().map(|c| *c) {
@@ -457,15 +696,10 @@ fn write_nonprint_to_end<W: Write>(in_buf: &[u8], writer: &mut W, tab: &[u8]) ->
             128..=159 => writer.write_all(&[b'M', b'-', b'^', byte - 64]),
             160..=254 => writer.write_all(&[b'M', b'-', byte - 128]),
             _ => writer.write_all(&[b'M', b'-', b'^', 63]),
-        }
-        .unwrap();
+        }?;
         count += 1;
     }
-    if count != in_buf.len() {
-        count + 1
-    } else {
-        0
-    }
+    Ok(if count != in_buf.len() { count + 1 } else { 0 })
 }
 
 #[cfg(test)]
@@ -477,7 +711,7 @@ mod tests {
         let mut writer = BufWriter::with_capacity(1024 * 64, stdout());
         let in_buf = b"\n";
         let tab = b"";
-        super::write_nonprint_to_end(in_buf, &mut writer, tab);
+        super::write_nonprint_to_end(in_buf, &mut writer, tab).unwrap();
         assert_eq!(writer.buffer().len(), 0);
     }
 
@@ -486,7 +720,7 @@ mod tests {
         let mut writer = BufWriter::with_capacity(1024 * 64, stdout());
         let in_buf = &[9u8];
         let tab = b"tab";
-        super::write_nonprint_to_end(in_buf, &mut writer, tab);
+        super::write_nonprint_to_end(in_buf, &mut writer, tab).unwrap();
         assert_eq!(writer.buffer(), tab);
     }
 
@@ -496,7 +730,7 @@ mod tests {
             let mut writer = BufWriter::with_capacity(1024 * 64, stdout());
             let in_buf = &[byte];
             let tab = b"";
-            super::write_nonprint_to_end(in_buf, &mut writer, tab);
+            super::write_nonprint_to_end(in_buf, &mut writer, tab).unwrap();
             assert_eq!(writer.buffer(), [b'^', byte + 64]);
         }
     }
@@ -507,7 +741,7 @@ mod tests {
             let mut writer = BufWriter::with_capacity(1024 * 64, stdout());
             let in_buf = &[byte];
             let tab = b"";
-            super::write_nonprint_to_end(in_buf, &mut writer, tab);
+            super::write_nonprint_to_end(in_buf, &mut writer, tab).unwrap();
             assert_eq!(writer.buffer(), [b'^', byte + 64]);
         }
     }